@@ -0,0 +1,402 @@
+//! A [`MemoryManager`] partitions a single [`StableMemory`] into up to
+//! [`MAX_MEMORIES`] independent [`VirtualMemory`] instances.
+//!
+//! Each [`VirtualMemory`] itself implements [`StableMemory`], so it can be
+//! dropped straight into [`StableWriter`](super::StableWriter),
+//! [`StableReader`](super::StableReader), or [`StableIO`](super::StableIO)
+//! in place of the underlying memory.
+//!
+//! The underlying memory is split into fixed-size buckets. A header at the
+//! start of the memory records, for each bucket, which virtual memory (if
+//! any) owns it. Because the header is the only state the manager keeps,
+//! re-creating a `MemoryManager` from the same underlying memory after a
+//! canister upgrade reconstructs the exact same set of virtual memories.
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use super::{StableMemory, StableMemoryError};
+
+const WASM_PAGE_SIZE_IN_BYTES: u64 = 64 * 1024;
+
+/// Magic bytes identifying a memory manager header.
+const MAGIC: &[u8; 3] = b"MGR";
+
+/// The current on-disk header layout version.
+const LAYOUT_VERSION: u8 = 2;
+
+/// The maximum number of virtual memories a single [`MemoryManager`] can hand out.
+pub const MAX_MEMORIES: u8 = 255;
+
+/// Marks a bucket in the allocation table as not yet assigned to any virtual memory.
+const UNALLOCATED_BUCKET_MARKER: u8 = 0xFF;
+
+/// The size, in WASM pages, of a single allocation bucket.
+const BUCKET_SIZE_IN_PAGES: u64 = 128;
+
+/// The maximum number of buckets the allocation table can describe.
+const MAX_NUM_BUCKETS: usize = 32768;
+
+/// Number of pages reserved at the start of the underlying memory for the
+/// header: the magic cookie, the version byte, the allocated bucket count,
+/// the bucket -> memory id table, and the per-memory logical page count
+/// table.
+const HEADER_RESERVED_PAGES: u64 = {
+    let header_bytes = 3 + 1 + 2 + MAX_NUM_BUCKETS as u64 + MAX_MEMORIES as u64 * 8;
+    (header_bytes + WASM_PAGE_SIZE_IN_BYTES - 1) / WASM_PAGE_SIZE_IN_BYTES
+};
+
+const MAGIC_OFFSET: u64 = 0;
+const VERSION_OFFSET: u64 = 3;
+const BUCKET_COUNT_OFFSET: u64 = 4;
+const BUCKET_TABLE_OFFSET: u64 = 6;
+const MEMORY_SIZES_OFFSET: u64 = BUCKET_TABLE_OFFSET + MAX_NUM_BUCKETS as u64;
+
+/// Partitions a [`StableMemory`] into up to [`MAX_MEMORIES`] independent
+/// [`VirtualMemory`] instances.
+///
+/// ```no_run
+/// use ic_cdk::api::stable::{CanisterStableMemory, MemoryManager};
+///
+/// let manager = MemoryManager::init(CanisterStableMemory::default());
+/// let log_memory = manager.get(0);
+/// let config_memory = manager.get(1);
+/// ```
+pub struct MemoryManager<M: StableMemory> {
+    inner: Rc<RefCell<MemoryManagerInner<M>>>,
+}
+
+impl<M: StableMemory> Clone for MemoryManager<M> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+struct MemoryManagerInner<M: StableMemory> {
+    memory: M,
+    /// One entry per bucket: the id of the virtual memory that owns it, or
+    /// [`UNALLOCATED_BUCKET_MARKER`] if the bucket is free.
+    bucket_table: Vec<u8>,
+    /// The number of buckets that have ever been handed out. Buckets beyond
+    /// this index have not yet been grown into and don't need to be scanned.
+    num_allocated_buckets: u16,
+    /// One entry per virtual memory id: its logical size in pages, as last
+    /// reported to `stable_grow`. This may be smaller than
+    /// `num_buckets_for(id) * BUCKET_SIZE_IN_PAGES`, since a virtual memory
+    /// only grows into a fresh bucket once it outgrows the buckets it
+    /// already owns.
+    memory_sizes: Vec<u64>,
+}
+
+impl<M: StableMemory> MemoryManager<M> {
+    /// Initializes a `MemoryManager` on top of `memory`.
+    ///
+    /// If `memory` already starts with a valid header (as written by a
+    /// previous instance of this manager, e.g. before a canister upgrade),
+    /// the existing bucket allocation is loaded back. Otherwise a fresh
+    /// header is written.
+    pub fn init(memory: M) -> Self {
+        if memory.stable64_size() * WASM_PAGE_SIZE_IN_BYTES >= HEADER_RESERVED_PAGES * WASM_PAGE_SIZE_IN_BYTES
+            && Self::read_magic(&memory) == *MAGIC
+        {
+            Self::load(memory)
+        } else {
+            Self::new(memory)
+        }
+    }
+
+    fn read_magic(memory: &M) -> [u8; 3] {
+        let mut magic = [0u8; 3];
+        memory.stable64_read(MAGIC_OFFSET, &mut magic);
+        magic
+    }
+
+    fn new(memory: M) -> Self {
+        if memory.stable64_size() < HEADER_RESERVED_PAGES {
+            let additional_pages = HEADER_RESERVED_PAGES - memory.stable64_size();
+            memory
+                .stable64_grow(additional_pages)
+                .expect("MemoryManager: failed to allocate header pages");
+        }
+
+        let inner = MemoryManagerInner {
+            memory,
+            bucket_table: vec![UNALLOCATED_BUCKET_MARKER; MAX_NUM_BUCKETS],
+            num_allocated_buckets: 0,
+            memory_sizes: vec![0u64; MAX_MEMORIES as usize],
+        };
+        let manager = Self {
+            inner: Rc::new(RefCell::new(inner)),
+        };
+        manager.write_header();
+        manager
+    }
+
+    fn load(memory: M) -> Self {
+        let version = {
+            let mut version = [0u8; 1];
+            memory.stable64_read(VERSION_OFFSET, &mut version);
+            version[0]
+        };
+        assert_eq!(
+            version, LAYOUT_VERSION,
+            "MemoryManager: unsupported header version {}",
+            version
+        );
+
+        let num_allocated_buckets = {
+            let mut bytes = [0u8; 2];
+            memory.stable64_read(BUCKET_COUNT_OFFSET, &mut bytes);
+            u16::from_le_bytes(bytes)
+        };
+
+        let mut bucket_table = vec![UNALLOCATED_BUCKET_MARKER; MAX_NUM_BUCKETS];
+        memory.stable64_read(BUCKET_TABLE_OFFSET, &mut bucket_table);
+
+        let mut memory_sizes_bytes = vec![0u8; MAX_MEMORIES as usize * 8];
+        memory.stable64_read(MEMORY_SIZES_OFFSET, &mut memory_sizes_bytes);
+        let memory_sizes = memory_sizes_bytes
+            .chunks_exact(8)
+            .map(|chunk| u64::from_le_bytes(chunk.try_into().unwrap()))
+            .collect();
+
+        let inner = MemoryManagerInner {
+            memory,
+            bucket_table,
+            num_allocated_buckets,
+            memory_sizes,
+        };
+        Self {
+            inner: Rc::new(RefCell::new(inner)),
+        }
+    }
+
+    fn write_header(&self) {
+        self.inner.borrow().write_header();
+    }
+
+    /// Returns the [`VirtualMemory`] with the given id, creating it if it
+    /// hasn't been used before.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `id >= MAX_MEMORIES`.
+    pub fn get(&self, id: u8) -> VirtualMemory<M> {
+        assert!(id < MAX_MEMORIES, "MemoryManager: memory id out of range");
+        VirtualMemory {
+            id,
+            manager: self.inner.clone(),
+        }
+    }
+}
+
+impl<M: StableMemory> MemoryManagerInner<M> {
+    fn write_header(&self) {
+        self.memory.stable64_write(MAGIC_OFFSET, MAGIC);
+        self.memory.stable64_write(VERSION_OFFSET, &[LAYOUT_VERSION]);
+        self.memory
+            .stable64_write(BUCKET_COUNT_OFFSET, &self.num_allocated_buckets.to_le_bytes());
+        self.memory.stable64_write(BUCKET_TABLE_OFFSET, &self.bucket_table);
+
+        let memory_sizes_bytes: Vec<u8> = self
+            .memory_sizes
+            .iter()
+            .flat_map(|size| size.to_le_bytes())
+            .collect();
+        self.memory
+            .stable64_write(MEMORY_SIZES_OFFSET, &memory_sizes_bytes);
+    }
+
+    /// The logical size, in pages, that `id` last reported via
+    /// `stable_grow`. This is independent of how many whole buckets have
+    /// been allocated to `id`, which may have slack beyond this size.
+    fn logical_page_count(&self, id: u8) -> u64 {
+        self.memory_sizes[id as usize]
+    }
+
+    /// Records `id`'s logical size, in pages.
+    fn set_logical_page_count(&mut self, id: u8, pages: u64) {
+        self.memory_sizes[id as usize] = pages;
+    }
+
+    /// The number of buckets currently owned by `id`.
+    fn num_buckets_for(&self, id: u8) -> usize {
+        self.bucket_table[..self.num_allocated_buckets as usize]
+            .iter()
+            .filter(|&&owner| owner == id)
+            .count()
+    }
+
+    /// Returns the physical bucket index that is the `logical_index`-th
+    /// bucket (in allocation order) owned by `id`.
+    fn physical_bucket(&self, id: u8, logical_index: usize) -> Option<usize> {
+        self.bucket_table[..self.num_allocated_buckets as usize]
+            .iter()
+            .enumerate()
+            .filter(|(_, &owner)| owner == id)
+            .nth(logical_index)
+            .map(|(physical_index, _)| physical_index)
+    }
+
+    /// Assigns the next free bucket to `id`, growing the underlying memory
+    /// first if no bucket has been reserved yet.
+    fn allocate_bucket(&mut self, id: u8) -> Result<(), StableMemoryError> {
+        let free_index = self.bucket_table[..self.num_allocated_buckets as usize]
+            .iter()
+            .position(|&owner| owner == UNALLOCATED_BUCKET_MARKER);
+
+        let bucket_index = match free_index {
+            Some(index) => index,
+            None => {
+                let index = self.num_allocated_buckets as usize;
+                if index >= MAX_NUM_BUCKETS {
+                    return Err(StableMemoryError::OutOfMemory);
+                }
+
+                let required_pages = HEADER_RESERVED_PAGES + (index as u64 + 1) * BUCKET_SIZE_IN_PAGES;
+                if self.memory.stable64_size() < required_pages {
+                    self.memory
+                        .stable64_grow(required_pages - self.memory.stable64_size())?;
+                }
+
+                self.num_allocated_buckets += 1;
+                index
+            }
+        };
+
+        self.bucket_table[bucket_index] = id;
+        Ok(())
+    }
+
+    fn physical_offset(&self, physical_bucket: usize, offset_in_bucket: u64) -> u64 {
+        HEADER_RESERVED_PAGES * WASM_PAGE_SIZE_IN_BYTES
+            + physical_bucket as u64 * BUCKET_SIZE_IN_PAGES * WASM_PAGE_SIZE_IN_BYTES
+            + offset_in_bucket
+    }
+}
+
+/// A virtual memory handed out by a [`MemoryManager`].
+///
+/// Implements [`StableMemory`], so it can be used anywhere a plain stable
+/// memory is expected.
+pub struct VirtualMemory<M: StableMemory> {
+    id: u8,
+    manager: Rc<RefCell<MemoryManagerInner<M>>>,
+}
+
+impl<M: StableMemory> Clone for VirtualMemory<M> {
+    fn clone(&self) -> Self {
+        Self {
+            id: self.id,
+            manager: self.manager.clone(),
+        }
+    }
+}
+
+impl<M: StableMemory> VirtualMemory<M> {
+    const BUCKET_SIZE_IN_BYTES: u64 = BUCKET_SIZE_IN_PAGES * WASM_PAGE_SIZE_IN_BYTES;
+
+    /// Splits an offset/length pair within this virtual memory into
+    /// `(logical_bucket_index, offset_in_bucket, chunk_len)` triples, one
+    /// per bucket the range touches.
+    fn chunks(offset: u64, len: u64) -> impl Iterator<Item = (usize, u64, u64)> {
+        let mut remaining = len;
+        let mut offset = offset;
+        std::iter::from_fn(move || {
+            if remaining == 0 {
+                return None;
+            }
+            let logical_bucket = (offset / Self::BUCKET_SIZE_IN_BYTES) as usize;
+            let offset_in_bucket = offset % Self::BUCKET_SIZE_IN_BYTES;
+            let chunk_len = remaining.min(Self::BUCKET_SIZE_IN_BYTES - offset_in_bucket);
+            offset += chunk_len;
+            remaining -= chunk_len;
+            Some((logical_bucket, offset_in_bucket, chunk_len))
+        })
+    }
+}
+
+impl<M: StableMemory> StableMemory for VirtualMemory<M> {
+    fn stable_size(&self) -> u32 {
+        self.stable64_size() as u32
+    }
+
+    fn stable64_size(&self) -> u64 {
+        let inner = self.manager.borrow();
+        inner.logical_page_count(self.id)
+    }
+
+    fn stable_grow(&self, new_pages: u32) -> Result<u32, StableMemoryError> {
+        self.stable64_grow(new_pages as u64).map(|old| old as u32)
+    }
+
+    fn stable64_grow(&self, new_pages: u64) -> Result<u64, StableMemoryError> {
+        let mut inner = self.manager.borrow_mut();
+        let old_page_count = inner.logical_page_count(self.id);
+        let new_page_count = old_page_count + new_pages;
+
+        let allocated_buckets = inner.num_buckets_for(self.id) as u64;
+        let required_buckets =
+            (new_page_count + BUCKET_SIZE_IN_PAGES - 1) / BUCKET_SIZE_IN_PAGES;
+        let mut additional_buckets = required_buckets.saturating_sub(allocated_buckets);
+        while additional_buckets > 0 {
+            inner.allocate_bucket(self.id)?;
+            additional_buckets -= 1;
+        }
+
+        inner.set_logical_page_count(self.id, new_page_count);
+        inner.write_header();
+
+        Ok(old_page_count)
+    }
+
+    fn stable_write(&self, offset: u32, buf: &[u8]) {
+        self.stable64_write(offset as u64, buf)
+    }
+
+    fn stable64_write(&self, offset: u64, buf: &[u8]) {
+        let inner = self.manager.borrow();
+        assert!(
+            offset + buf.len() as u64
+                <= inner.logical_page_count(self.id) * WASM_PAGE_SIZE_IN_BYTES,
+            "VirtualMemory: write out of bounds"
+        );
+        let mut written = 0usize;
+        for (logical_bucket, offset_in_bucket, chunk_len) in Self::chunks(offset, buf.len() as u64) {
+            let physical_bucket = inner
+                .physical_bucket(self.id, logical_bucket)
+                .expect("VirtualMemory: write out of bounds");
+            let physical_offset = inner.physical_offset(physical_bucket, offset_in_bucket);
+            inner
+                .memory
+                .stable64_write(physical_offset, &buf[written..written + chunk_len as usize]);
+            written += chunk_len as usize;
+        }
+    }
+
+    fn stable_read(&self, offset: u32, buf: &mut [u8]) {
+        self.stable64_read(offset as u64, buf)
+    }
+
+    fn stable64_read(&self, offset: u64, buf: &mut [u8]) {
+        let inner = self.manager.borrow();
+        assert!(
+            offset + buf.len() as u64
+                <= inner.logical_page_count(self.id) * WASM_PAGE_SIZE_IN_BYTES,
+            "VirtualMemory: read out of bounds"
+        );
+        let mut read = 0usize;
+        for (logical_bucket, offset_in_bucket, chunk_len) in Self::chunks(offset, buf.len() as u64) {
+            let physical_bucket = inner
+                .physical_bucket(self.id, logical_bucket)
+                .expect("VirtualMemory: read out of bounds");
+            let physical_offset = inner.physical_offset(physical_bucket, offset_in_bucket);
+            inner
+                .memory
+                .stable64_read(physical_offset, &mut buf[read..read + chunk_len as usize]);
+            read += chunk_len as usize;
+        }
+    }
+}
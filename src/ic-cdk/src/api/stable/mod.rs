@@ -5,11 +5,16 @@
 
 mod canister;
 mod canister_static;
+mod memory_manager;
 mod private;
+mod stable_cell;
 mod stable_io;
 mod stable_memory;
 mod stable_reader;
+mod stable_vec;
 mod stable_writer;
+mod storable;
+mod vector_memory;
 
 #[cfg(test)]
 mod tests;
@@ -21,11 +26,21 @@ pub use canister_static::{
     stable_read, stable_size, stable_write,
 };
 
+pub use memory_manager::{MemoryManager, VirtualMemory, MAX_MEMORIES};
+
+pub use stable_cell::StableCell;
+
 pub use stable_memory::{StableMemory, StableMemoryError};
 
 pub use stable_reader::{BufferedStableReader, StableReader};
 
-pub use stable_writer::{BufferedStableWriter, StableWriter};
+pub use stable_vec::StableVec;
+
+pub use stable_writer::{BufferedStableWriter, IntoInnerError, StableWriter};
+
+pub use storable::Storable;
+
+pub use vector_memory::{DefaultMemoryImpl, FileMemory, VectorMemory};
 
 use stable_io::StableIO;
 
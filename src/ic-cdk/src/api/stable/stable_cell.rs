@@ -0,0 +1,91 @@
+use super::{CanisterStableMemory, StableMemory, StableReader, StableWriter, Storable};
+
+/// A single value of type `T` persisted at a fixed location in stable
+/// memory, surviving canister upgrades.
+///
+/// `StableCell` reads its value back from `memory` on construction, so
+/// canister authors no longer need to serialize this piece of state into
+/// `stable_bytes()` in `pre_upgrade` and deserialize it again in
+/// `post_upgrade`.
+///
+/// When `T::IS_FIXED_SIZE` is `false`, the value is stored behind a 4-byte
+/// length prefix; when it's `true`, `to_bytes` is known to always produce
+/// exactly `T::MAX_SIZE` bytes, so the prefix is skipped.
+pub struct StableCell<T: Storable, M: StableMemory + Clone = CanisterStableMemory> {
+    memory: M,
+    value: T,
+}
+
+impl<T: Storable, M: StableMemory + Clone> StableCell<T, M> {
+    /// Initializes a cell backed by `memory`.
+    ///
+    /// If `memory` already holds a previously written value (its size is
+    /// non-zero), that value is read back and returned. Otherwise
+    /// `default_value` is written to `memory` and returned.
+    pub fn init(memory: M, default_value: T) -> Self {
+        if memory.stable_size() == 0 {
+            let cell = Self {
+                memory,
+                value: default_value,
+            };
+            cell.write();
+            cell
+        } else {
+            let mut reader = StableReader::with_memory(memory.clone(), 0);
+
+            let len = if T::IS_FIXED_SIZE {
+                T::MAX_SIZE as usize
+            } else {
+                let mut len_bytes = [0u8; 4];
+                reader
+                    .read(&mut len_bytes)
+                    .expect("StableCell: failed to read length header");
+                u32::from_le_bytes(len_bytes) as usize
+            };
+
+            let mut bytes = vec![0u8; len];
+            reader
+                .read(&mut bytes)
+                .expect("StableCell: failed to read value");
+
+            let value = T::from_bytes(bytes.into());
+            Self { memory, value }
+        }
+    }
+
+    /// Returns a reference to the current value.
+    pub fn get(&self) -> &T {
+        &self.value
+    }
+
+    /// Replaces the current value, persisting it to stable memory.
+    pub fn set(&mut self, value: T) {
+        self.value = value;
+        self.write();
+    }
+
+    fn write(&self) {
+        let bytes = self.value.to_bytes();
+        if T::IS_FIXED_SIZE {
+            assert!(
+                bytes.len() as u32 == T::MAX_SIZE,
+                "StableCell: value does not match Storable::MAX_SIZE, but Storable::IS_FIXED_SIZE is true"
+            );
+        } else {
+            assert!(
+                bytes.len() as u32 <= T::MAX_SIZE,
+                "StableCell: value exceeds Storable::MAX_SIZE"
+            );
+        }
+
+        let mut writer = StableWriter::with_memory(self.memory.clone(), 0);
+        if !T::IS_FIXED_SIZE {
+            writer
+                .write(&(bytes.len() as u32).to_le_bytes())
+                .expect("StableCell: failed to write length header");
+        }
+        writer
+            .write(&bytes)
+            .expect("StableCell: failed to write value");
+    }
+}
@@ -22,6 +22,11 @@ pub struct StableIO<M: StableMemory = CanisterStableMemory, A: private::AddressS
 
     /// The stable memory to write data to.
     memory: M,
+
+    /// Whether a read that would exceed the cached `capacity` should
+    /// re-query the memory's actual size before failing. See
+    /// [`StableIO::set_refresh_size`].
+    refresh_size: bool,
 }
 
 impl Default for StableIO {
@@ -45,9 +50,23 @@ macro_rules! impl_stable_io {
                     offset,
                     capacity,
                     memory,
+                    refresh_size: false,
                 }
             }
 
+            /// Sets whether a read that would exceed the cached `capacity`
+            /// should re-query the memory's actual size before deciding the
+            /// read is out of bounds, rather than trusting the size cached
+            /// when this `StableIO` was created.
+            ///
+            /// This is off by default, since checking the size on every
+            /// over-capacity read costs an extra system call; long-lived
+            /// readers that expect the memory to keep growing underneath
+            /// them should turn it on.
+            pub fn set_refresh_size(&mut self, refresh_size: bool) {
+                self.refresh_size = refresh_size;
+            }
+
             /// Returns the offset of the writer
             pub fn offset(&self) -> $address {
                 self.offset
@@ -60,12 +79,10 @@ macro_rules! impl_stable_io {
                 Ok(())
             }
 
-            /// Writes a byte slice to the buffer.
-            ///
-            /// The only condition where this will
-            /// error out is if it cannot grow the memory.
-            pub fn write(&mut self, buf: &[u8]) -> Result<usize, StableMemoryError> {
-                let required_capacity_bytes = self.offset + buf.len() as $address;
+            // Grows the memory, if needed, so that `additional_bytes` more bytes can be
+            // written at the current offset.
+            fn ensure_capacity(&mut self, additional_bytes: $address) -> Result<(), StableMemoryError> {
+                let required_capacity_bytes = self.offset + additional_bytes;
                 let required_capacity_pages =
                     ((required_capacity_bytes + WASM_PAGE_SIZE_IN_BYTES as $address - 1)
                         / WASM_PAGE_SIZE_IN_BYTES as $address);
@@ -77,21 +94,57 @@ macro_rules! impl_stable_io {
                     self.grow(additional_pages_required)?;
                 }
 
+                Ok(())
+            }
+
+            /// Writes a byte slice to the buffer.
+            ///
+            /// The only condition where this will
+            /// error out is if it cannot grow the memory.
+            pub fn write(&mut self, buf: &[u8]) -> Result<usize, StableMemoryError> {
+                self.ensure_capacity(buf.len() as $address)?;
+
                 self.memory.stable_write_(self.offset, buf);
                 self.offset += buf.len() as $address;
                 Ok(buf.len())
             }
 
+            /// Writes a set of byte slices to the buffer, computing the total
+            /// required capacity up front so the memory is grown at most once
+            /// regardless of how many slices are passed.
+            pub fn write_vectored(
+                &mut self,
+                bufs: &[io::IoSlice<'_>],
+            ) -> Result<usize, StableMemoryError> {
+                let total_len: $address = bufs.iter().map(|buf| buf.len() as $address).sum();
+                self.ensure_capacity(total_len)?;
+
+                let mut written = 0usize;
+                for buf in bufs {
+                    self.memory.stable_write_(self.offset, buf);
+                    self.offset += buf.len() as $address;
+                    written += buf.len();
+                }
+                Ok(written)
+            }
+
             /// Reads data from the stable memory location specified by an offset.
             ///
             /// Note:
-            /// The stable memory size is cached on creation of the StableReader.
+            /// The stable memory size is cached on creation of the `StableIO`.
             /// Therefore, in following scenario, it will get an `OutOfBounds` error:
-            /// 1. Create a StableReader
+            /// 1. Create a `StableIO`
             /// 2. Write some data to the stable memory which causes it grow
             /// 3. call `read()` to read the newly written bytes
+            ///
+            /// Call [`StableIO::set_refresh_size`] to re-query the actual
+            /// memory size instead of returning `OutOfBounds` in this case.
             pub fn read(&mut self, buf: &mut [u8]) -> Result<usize, StableMemoryError> {
-                let capacity_bytes = self.capacity * WASM_PAGE_SIZE_IN_BYTES as $address;
+                let mut capacity_bytes = self.capacity * WASM_PAGE_SIZE_IN_BYTES as $address;
+                if self.refresh_size && buf.len() as $address + self.offset > capacity_bytes {
+                    self.capacity = self.memory.stable_size_();
+                    capacity_bytes = self.capacity * WASM_PAGE_SIZE_IN_BYTES as $address;
+                }
                 let read_buf = if buf.len() as $address + self.offset > capacity_bytes {
                     if self.offset < capacity_bytes {
                         &mut buf[..(capacity_bytes - self.offset) as usize]
@@ -106,6 +159,46 @@ macro_rules! impl_stable_io {
                 Ok(read_buf.len())
             }
 
+            /// Reads data into a set of byte slices, filling them in order.
+            /// The size refresh (if enabled) and the out-of-bounds check are
+            /// done once for the whole batch; each slice is then clipped to
+            /// the remaining capacity, and reading stops at the first slice
+            /// that is not filled completely.
+            pub fn read_vectored(
+                &mut self,
+                bufs: &mut [io::IoSliceMut<'_>],
+            ) -> Result<usize, StableMemoryError> {
+                let total_len: usize = bufs.iter().map(|buf| buf.len()).sum();
+                let mut capacity_bytes = self.capacity * WASM_PAGE_SIZE_IN_BYTES as $address;
+                if self.refresh_size && total_len as $address + self.offset > capacity_bytes {
+                    self.capacity = self.memory.stable_size_();
+                    capacity_bytes = self.capacity * WASM_PAGE_SIZE_IN_BYTES as $address;
+                }
+
+                if total_len > 0 && self.offset >= capacity_bytes {
+                    return Err(StableMemoryError::OutOfBounds);
+                }
+
+                let mut read = 0usize;
+                for buf in bufs.iter_mut() {
+                    if self.offset >= capacity_bytes {
+                        break;
+                    }
+
+                    let available = (capacity_bytes - self.offset) as usize;
+                    let len = buf.len().min(available);
+
+                    self.memory.stable_read_(self.offset, &mut buf[..len]);
+                    self.offset += len as $address;
+                    read += len;
+
+                    if len < buf.len() {
+                        break;
+                    }
+                }
+                Ok(read)
+            }
+
             // Helper used to implement io::Seek
             fn seek(&mut self, offset: io::SeekFrom) -> io::Result<u64> {
                 self.offset = match offset {
@@ -129,6 +222,15 @@ macro_rules! impl_stable_io {
                     .map_err(|e| io::Error::new(io::ErrorKind::OutOfMemory, e))
             }
 
+            fn write_vectored(&mut self, bufs: &[io::IoSlice<'_>]) -> Result<usize, io::Error> {
+                Self::write_vectored(self, bufs)
+                    .map_err(|e| io::Error::new(io::ErrorKind::OutOfMemory, e))
+            }
+
+            fn is_write_vectored(&self) -> bool {
+                true
+            }
+
             fn flush(&mut self) -> Result<(), io::Error> {
                 // Noop.
                 Ok(())
@@ -141,6 +243,10 @@ macro_rules! impl_stable_io {
             fn read(&mut self, buf: &mut [u8]) -> Result<usize, io::Error> {
                 Self::read(self, buf).or(Ok(0)) // Read defines EOF to be success
             }
+
+            fn read_vectored(&mut self, bufs: &mut [io::IoSliceMut<'_>]) -> Result<usize, io::Error> {
+                Self::read_vectored(self, bufs).or(Ok(0)) // Read defines EOF to be success
+            }
         }
 
         impl<M: private::StableMemory_<$address> + StableMemory> io::Seek
@@ -20,6 +20,21 @@ impl<M: StableMemory> StableReader<M> {
         Self(StableIO::<M, u32>::with_memory(memory, offset as u32))
     }
 
+    /// Creates a new `StableReader` which reads from the selected memory and
+    /// re-checks the memory's actual size whenever a read would otherwise
+    /// fail because it exceeds the size cached at construction time.
+    ///
+    /// Use this for long-lived readers that are expected to observe data
+    /// written (and memory grown) after the reader itself was created; it
+    /// costs an extra size query on every such read, so prefer
+    /// [`StableReader::with_memory`] when that isn't needed.
+    #[inline]
+    pub fn with_memory_refreshing(memory: M, offset: usize) -> Self {
+        let mut io = StableIO::<M, u32>::with_memory(memory, offset as u32);
+        io.set_refresh_size(true);
+        Self(io)
+    }
+
     /// Returns the offset of the reader
     #[inline]
     pub fn offset(&self) -> usize {
@@ -34,10 +49,20 @@ impl<M: StableMemory> StableReader<M> {
     /// 1. Create a StableReader
     /// 2. Write some data to the stable memory which causes it grow
     /// 3. call `read()` to read the newly written bytes
+    ///
+    /// Use [`StableReader::with_memory_refreshing`] to have the reader
+    /// re-check the memory's actual size instead of failing in this case.
     #[inline]
     pub fn read(&mut self, buf: &mut [u8]) -> Result<usize, StableMemoryError> {
         self.0.read(buf)
     }
+
+    /// Reads data into a set of byte slices, filling them in order and
+    /// stopping at the end of the remaining capacity.
+    #[inline]
+    pub fn read_vectored(&mut self, bufs: &mut [io::IoSliceMut<'_>]) -> Result<usize, StableMemoryError> {
+        self.0.read_vectored(bufs)
+    }
 }
 
 impl<M: StableMemory> io::Read for StableReader<M> {
@@ -45,6 +70,11 @@ impl<M: StableMemory> io::Read for StableReader<M> {
     fn read(&mut self, buf: &mut [u8]) -> Result<usize, io::Error> {
         io::Read::read(&mut self.0, buf)
     }
+
+    #[inline]
+    fn read_vectored(&mut self, bufs: &mut [io::IoSliceMut<'_>]) -> Result<usize, io::Error> {
+        io::Read::read_vectored(&mut self.0, bufs)
+    }
 }
 
 impl<M: StableMemory> io::Seek for StableReader<M> {
@@ -84,6 +114,30 @@ impl<M: StableMemory> BufferedStableReader<M> {
     pub fn offset(&self) -> usize {
         self.inner.get_ref().offset()
     }
+
+    /// Gets a reference to the underlying `StableReader`.
+    ///
+    /// It is inadvisable to directly read from the underlying reader, as
+    /// doing so may corrupt the buffer held by this `BufferedStableReader`.
+    pub fn get_ref(&self) -> &StableReader<M> {
+        self.inner.get_ref()
+    }
+
+    /// Gets a mutable reference to the underlying `StableReader`.
+    ///
+    /// It is inadvisable to directly read from the underlying reader, as
+    /// doing so may corrupt the buffer held by this `BufferedStableReader`.
+    pub fn get_mut(&mut self) -> &mut StableReader<M> {
+        self.inner.get_mut()
+    }
+
+    /// Unwraps this `BufferedStableReader`, returning the underlying
+    /// `StableReader`.
+    ///
+    /// Note that any leftover data in the internal buffer is lost.
+    pub fn into_inner(self) -> StableReader<M> {
+        self.inner.into_inner()
+    }
 }
 
 impl<M: StableMemory> io::Read for BufferedStableReader<M> {
@@ -92,6 +146,16 @@ impl<M: StableMemory> io::Read for BufferedStableReader<M> {
     }
 }
 
+impl<M: StableMemory> io::BufRead for BufferedStableReader<M> {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        self.inner.fill_buf()
+    }
+
+    fn consume(&mut self, amt: usize) {
+        self.inner.consume(amt)
+    }
+}
+
 impl<M: StableMemory> io::Seek for BufferedStableReader<M> {
     #[inline]
     fn seek(&mut self, pos: io::SeekFrom) -> io::Result<u64> {
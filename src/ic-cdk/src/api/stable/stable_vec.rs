@@ -0,0 +1,146 @@
+use std::marker::PhantomData;
+
+use super::{CanisterStableMemory, StableMemory, StableReader, StableWriter, Storable};
+
+const LEN_HEADER_SIZE: u64 = 4;
+
+/// A growable vector of `T` persisted in stable memory, surviving canister
+/// upgrades.
+///
+/// The memory layout is a 4-byte length header followed by fixed-width
+/// slots, each large enough to hold `T::MAX_SIZE` bytes. When
+/// `T::IS_FIXED_SIZE` is `false`, every slot additionally carries a 4-byte
+/// per-slot length prefix; when it's `true`, `to_bytes` is known to always
+/// produce exactly `T::MAX_SIZE` bytes, so the prefix is skipped entirely.
+pub struct StableVec<T: Storable, M: StableMemory + Clone = CanisterStableMemory> {
+    memory: M,
+    len: u64,
+    _marker: PhantomData<T>,
+}
+
+impl<T: Storable, M: StableMemory + Clone> StableVec<T, M> {
+    fn slot_size() -> u64 {
+        if T::IS_FIXED_SIZE {
+            T::MAX_SIZE as u64
+        } else {
+            4 + T::MAX_SIZE as u64
+        }
+    }
+
+    /// Initializes a vector backed by `memory`, reading back the length
+    /// header written by a previous instance (if any).
+    pub fn init(memory: M) -> Self {
+        if memory.stable_size() == 0 {
+            let vec = Self {
+                memory,
+                len: 0,
+                _marker: PhantomData,
+            };
+            vec.write_len();
+            vec
+        } else {
+            let mut reader = StableReader::with_memory(memory.clone(), 0);
+            let mut len_bytes = [0u8; 4];
+            reader
+                .read(&mut len_bytes)
+                .expect("StableVec: failed to read length header");
+            Self {
+                memory,
+                len: u32::from_le_bytes(len_bytes) as u64,
+                _marker: PhantomData,
+            }
+        }
+    }
+
+    /// The number of elements currently stored.
+    pub fn len(&self) -> u64 {
+        self.len
+    }
+
+    /// Returns `true` if the vector has no elements.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Reads the element at `index`, or `None` if `index >= len()`.
+    pub fn get(&self, index: u64) -> Option<T> {
+        if index >= self.len {
+            return None;
+        }
+
+        let mut reader =
+            StableReader::with_memory(self.memory.clone(), self.slot_offset(index) as usize);
+
+        let value_len = if T::IS_FIXED_SIZE {
+            T::MAX_SIZE as usize
+        } else {
+            let mut len_bytes = [0u8; 4];
+            reader
+                .read(&mut len_bytes)
+                .expect("StableVec: failed to read slot length");
+            u32::from_le_bytes(len_bytes) as usize
+        };
+
+        let mut bytes = vec![0u8; value_len];
+        reader
+            .read(&mut bytes)
+            .expect("StableVec: failed to read slot value");
+
+        Some(T::from_bytes(bytes.into()))
+    }
+
+    /// Overwrites the element at `index`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index >= len()`.
+    pub fn set(&mut self, index: u64, value: &T) {
+        assert!(index < self.len, "StableVec: index out of bounds");
+        self.write_slot(index, value);
+    }
+
+    /// Appends `value` to the end of the vector.
+    pub fn push(&mut self, value: &T) {
+        let index = self.len;
+        self.write_slot(index, value);
+        self.len += 1;
+        self.write_len();
+    }
+
+    fn slot_offset(&self, index: u64) -> u64 {
+        LEN_HEADER_SIZE + index * Self::slot_size()
+    }
+
+    fn write_slot(&self, index: u64, value: &T) {
+        let bytes = value.to_bytes();
+        if T::IS_FIXED_SIZE {
+            assert!(
+                bytes.len() as u32 == T::MAX_SIZE,
+                "StableVec: value does not match Storable::MAX_SIZE, but Storable::IS_FIXED_SIZE is true"
+            );
+        } else {
+            assert!(
+                bytes.len() as u32 <= T::MAX_SIZE,
+                "StableVec: value exceeds Storable::MAX_SIZE"
+            );
+        }
+
+        let mut writer =
+            StableWriter::with_memory(self.memory.clone(), self.slot_offset(index) as usize);
+        if !T::IS_FIXED_SIZE {
+            writer
+                .write(&(bytes.len() as u32).to_le_bytes())
+                .expect("StableVec: failed to write slot length");
+        }
+        writer
+            .write(&bytes)
+            .expect("StableVec: failed to write slot value");
+    }
+
+    fn write_len(&self) {
+        let mut writer = StableWriter::with_memory(self.memory.clone(), 0);
+        writer
+            .write(&(self.len as u32).to_le_bytes())
+            .expect("StableVec: failed to write length header");
+    }
+}
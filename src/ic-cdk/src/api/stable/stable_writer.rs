@@ -1,4 +1,4 @@
-use std::io;
+use std::{error, fmt, io};
 
 use super::{CanisterStableMemory, StableIO, StableMemory, StableMemoryError};
 
@@ -45,6 +45,13 @@ impl<M: StableMemory> StableWriter<M> {
     pub fn write(&mut self, buf: &[u8]) -> Result<usize, StableMemoryError> {
         self.0.write(buf)
     }
+
+    /// Writes a set of byte slices, growing the memory at most once for the
+    /// whole batch rather than once per slice.
+    #[inline]
+    pub fn write_vectored(&mut self, bufs: &[io::IoSlice<'_>]) -> Result<usize, StableMemoryError> {
+        self.0.write_vectored(bufs)
+    }
 }
 
 impl<M: StableMemory> io::Write for StableWriter<M> {
@@ -53,6 +60,16 @@ impl<M: StableMemory> io::Write for StableWriter<M> {
         io::Write::write(&mut self.0, buf)
     }
 
+    #[inline]
+    fn write_vectored(&mut self, bufs: &[io::IoSlice<'_>]) -> Result<usize, io::Error> {
+        io::Write::write_vectored(&mut self.0, bufs)
+    }
+
+    #[inline]
+    fn is_write_vectored(&self) -> bool {
+        true
+    }
+
     #[inline]
     fn flush(&mut self) -> Result<(), io::Error> {
         io::Write::flush(&mut self.0)
@@ -103,6 +120,42 @@ impl<M: StableMemory> BufferedStableWriter<M> {
     pub fn offset(&self) -> usize {
         self.inner.get_ref().offset()
     }
+
+    /// Gets a reference to the underlying `StableWriter`.
+    ///
+    /// It is inadvisable to directly write to the underlying writer, as
+    /// doing so may corrupt the buffer held by this `BufferedStableWriter`.
+    pub fn get_ref(&self) -> &StableWriter<M> {
+        self.inner.get_ref()
+    }
+
+    /// Gets a mutable reference to the underlying `StableWriter`.
+    ///
+    /// It is inadvisable to directly write to the underlying writer, as
+    /// doing so may corrupt the buffer held by this `BufferedStableWriter`.
+    pub fn get_mut(&mut self) -> &mut StableWriter<M> {
+        self.inner.get_mut()
+    }
+
+    /// Unwraps this `BufferedStableWriter`, flushing the buffer to stable
+    /// memory and returning the underlying `StableWriter`.
+    ///
+    /// If the flush fails (e.g. because stable memory could not be grown),
+    /// the returned error carries both the failed buffered writer and the
+    /// `io::Error` that caused the failure, so no buffered bytes are
+    /// silently lost.
+    pub fn into_inner(self) -> Result<StableWriter<M>, IntoInnerError<BufferedStableWriter<M>>> {
+        match self.inner.into_inner() {
+            Ok(writer) => Ok(writer),
+            Err(err) => {
+                let (error, inner) = err.into_parts();
+                Err(IntoInnerError::new(
+                    BufferedStableWriter { inner },
+                    error,
+                ))
+            }
+        }
+    }
 }
 
 impl<M: StableMemory> io::Write for BufferedStableWriter<M> {
@@ -121,3 +174,52 @@ impl<M: StableMemory> io::Seek for BufferedStableWriter<M> {
         io::Seek::seek(&mut self.inner, pos)
     }
 }
+
+/// The error returned by [`BufferedStableWriter::into_inner`] when the
+/// buffer could not be fully flushed to stable memory.
+///
+/// Mirrors the contract of `std::io::IntoInnerError`: it carries both the
+/// writer (with its unflushed data still buffered) and the underlying
+/// `io::Error`, so the caller can recover and retry instead of losing
+/// buffered bytes.
+pub struct IntoInnerError<W> {
+    writer: W,
+    error: io::Error,
+}
+
+impl<W> IntoInnerError<W> {
+    fn new(writer: W, error: io::Error) -> Self {
+        Self { writer, error }
+    }
+
+    /// Returns the error that caused the flush to fail.
+    pub fn error(&self) -> &io::Error {
+        &self.error
+    }
+
+    /// Returns the writer, discarding the `io::Error`. The writer still
+    /// holds any data that could not be flushed.
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+
+    /// Consumes this error, returning both the underlying `io::Error` and
+    /// the writer.
+    pub fn into_parts(self) -> (io::Error, W) {
+        (self.error, self.writer)
+    }
+}
+
+impl<W> fmt::Debug for IntoInnerError<W> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.error.fmt(f)
+    }
+}
+
+impl<W> fmt::Display for IntoInnerError<W> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.error.fmt(f)
+    }
+}
+
+impl<W> error::Error for IntoInnerError<W> {}
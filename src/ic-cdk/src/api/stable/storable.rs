@@ -0,0 +1,21 @@
+//! The [`Storable`] trait, used by [`StableCell`](super::StableCell) and
+//! [`StableVec`](super::StableVec) to (de)serialize values kept in stable
+//! memory across canister upgrades.
+
+use std::borrow::Cow;
+
+/// A trait for values that can be persisted directly in stable memory.
+pub trait Storable {
+    /// Converts `self` to its byte representation.
+    fn to_bytes(&self) -> Cow<[u8]>;
+
+    /// Converts bytes previously produced by `to_bytes` back into `Self`.
+    fn from_bytes(bytes: Cow<[u8]>) -> Self;
+
+    /// The maximum number of bytes `to_bytes` can return for this type.
+    const MAX_SIZE: u32;
+
+    /// Whether `to_bytes` always returns exactly `MAX_SIZE` bytes (`true`),
+    /// as opposed to at most `MAX_SIZE` bytes (`false`).
+    const IS_FIXED_SIZE: bool;
+}
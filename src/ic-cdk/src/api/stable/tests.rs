@@ -0,0 +1,326 @@
+use std::borrow::Cow;
+use std::fs::{self, OpenOptions};
+use std::io::{self, BufRead, Seek, SeekFrom, Write};
+
+use super::{
+    BufferedStableReader, BufferedStableWriter, FileMemory, MemoryManager, StableCell,
+    StableMemory, StableReader, StableVec, StableWriter, Storable, VectorMemory,
+    WASM_PAGE_SIZE_IN_BYTES,
+};
+
+/// Opens a fresh, empty temp file for a `FileMemory` test, removing any
+/// leftover file from a previous run.
+fn temp_file(name: &str) -> fs::File {
+    let path = std::env::temp_dir().join(format!("ic-cdk-stable-tests-{name}"));
+    let _ = fs::remove_file(&path);
+    OpenOptions::new()
+        .read(true)
+        .write(true)
+        .create(true)
+        .open(&path)
+        .expect("failed to create temp file")
+}
+
+fn round_trip<M: StableMemory>(memory: M) {
+    assert_eq!(memory.stable_size(), 0);
+
+    let old_page_count = memory.stable_grow(2).expect("grow should succeed");
+    assert_eq!(old_page_count, 0);
+    assert_eq!(memory.stable_size(), 2);
+
+    let old_page_count = memory.stable_grow(1).expect("grow should succeed");
+    assert_eq!(old_page_count, 2);
+    assert_eq!(memory.stable_size(), 3);
+
+    memory.stable_write(0, b"hello");
+    memory.stable_write(WASM_PAGE_SIZE_IN_BYTES as u32, b"world");
+
+    let mut buf = [0u8; 5];
+    memory.stable_read(0, &mut buf);
+    assert_eq!(&buf, b"hello");
+
+    memory.stable_read(WASM_PAGE_SIZE_IN_BYTES as u32, &mut buf);
+    assert_eq!(&buf, b"world");
+}
+
+#[test]
+fn vector_memory_round_trip() {
+    round_trip(VectorMemory::default());
+}
+
+#[test]
+fn file_memory_round_trip() {
+    let file = temp_file("round-trip");
+    round_trip(FileMemory::new(file));
+}
+
+#[test]
+#[should_panic(expected = "write exceeds the current size")]
+fn vector_memory_write_out_of_bounds_panics() {
+    let memory = VectorMemory::default();
+    memory.stable_grow(1).unwrap();
+    memory.stable_write(WASM_PAGE_SIZE_IN_BYTES as u32, b"x");
+}
+
+#[test]
+#[should_panic(expected = "read exceeds the current size")]
+fn vector_memory_read_out_of_bounds_panics() {
+    let memory = VectorMemory::default();
+    memory.stable_grow(1).unwrap();
+    let mut buf = [0u8; 1];
+    memory.stable_read(WASM_PAGE_SIZE_IN_BYTES as u32, &mut buf);
+}
+
+#[test]
+#[should_panic(expected = "write exceeds the current size")]
+fn file_memory_write_out_of_bounds_panics() {
+    let file = temp_file("write-oob");
+    let memory = FileMemory::new(file);
+    memory.stable_grow(1).unwrap();
+    memory.stable_write(WASM_PAGE_SIZE_IN_BYTES as u32, b"x");
+}
+
+#[test]
+#[should_panic(expected = "read exceeds the current size")]
+fn file_memory_read_out_of_bounds_panics() {
+    let file = temp_file("read-oob");
+    let memory = FileMemory::new(file);
+    memory.stable_grow(1).unwrap();
+    let mut buf = [0u8; 1];
+    memory.stable_read(WASM_PAGE_SIZE_IN_BYTES as u32, &mut buf);
+}
+
+// Matches `memory_manager::BUCKET_SIZE_IN_PAGES`, which isn't exported.
+const BUCKET_SIZE_IN_PAGES: u64 = 128;
+
+#[test]
+fn memory_manager_virtual_memory_write_read_crosses_bucket_boundary() {
+    let manager = MemoryManager::init(VectorMemory::default());
+    let vm = manager.get(0);
+
+    // Grow past a single bucket so the write below spans two buckets.
+    vm.stable64_grow(BUCKET_SIZE_IN_PAGES + 1).unwrap();
+
+    let page_bytes = WASM_PAGE_SIZE_IN_BYTES as u64;
+    let offset = BUCKET_SIZE_IN_PAGES * page_bytes - 4;
+    vm.stable64_write(offset, b"boundary");
+
+    let mut buf = [0u8; 8];
+    vm.stable64_read(offset, &mut buf);
+    assert_eq!(&buf, b"boundary");
+}
+
+#[test]
+fn memory_manager_reload_reconstructs_bucket_ownership_and_sizes() {
+    let memory = VectorMemory::default();
+    // `VectorMemory` shares its backing `Vec<u8>` through an `Rc`, so
+    // cloning it here simulates reading back the same underlying stable
+    // memory after a canister upgrade.
+    let manager = MemoryManager::init(memory.clone());
+
+    let vm0 = manager.get(0);
+    vm0.stable_grow(1).unwrap();
+    vm0.stable_write(0, b"vm0");
+
+    let vm1 = manager.get(1);
+    vm1.stable_grow(2).unwrap();
+    vm1.stable_write(0, b"vm1!");
+
+    let reloaded = MemoryManager::init(memory);
+
+    let vm0 = reloaded.get(0);
+    assert_eq!(vm0.stable_size(), 1);
+    let mut buf = [0u8; 3];
+    vm0.stable_read(0, &mut buf);
+    assert_eq!(&buf, b"vm0");
+
+    let vm1 = reloaded.get(1);
+    assert_eq!(vm1.stable_size(), 2);
+    let mut buf = [0u8; 4];
+    vm1.stable_read(0, &mut buf);
+    assert_eq!(&buf, b"vm1!");
+}
+
+/// A `Storable` whose `to_bytes` always produces exactly `MAX_SIZE` bytes.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+struct FixedVal(u32);
+
+impl Storable for FixedVal {
+    fn to_bytes(&self) -> Cow<[u8]> {
+        Cow::Owned(self.0.to_le_bytes().to_vec())
+    }
+
+    fn from_bytes(bytes: Cow<[u8]>) -> Self {
+        let mut array = [0u8; 4];
+        array.copy_from_slice(&bytes);
+        Self(u32::from_le_bytes(array))
+    }
+
+    const MAX_SIZE: u32 = 4;
+    const IS_FIXED_SIZE: bool = true;
+}
+
+/// A `Storable` whose `to_bytes` produces at most `MAX_SIZE` bytes.
+#[derive(Clone, PartialEq, Eq, Debug)]
+struct BoundedVal(String);
+
+impl Storable for BoundedVal {
+    fn to_bytes(&self) -> Cow<[u8]> {
+        Cow::Owned(self.0.as_bytes().to_vec())
+    }
+
+    fn from_bytes(bytes: Cow<[u8]>) -> Self {
+        Self(String::from_utf8(bytes.into_owned()).expect("valid utf8"))
+    }
+
+    const MAX_SIZE: u32 = 16;
+    const IS_FIXED_SIZE: bool = false;
+}
+
+#[test]
+fn stable_cell_round_trips_fixed_size_value_through_reinit() {
+    let memory = VectorMemory::default();
+    let mut cell = StableCell::init(memory.clone(), FixedVal(0));
+    cell.set(FixedVal(42));
+
+    let cell = StableCell::init(memory, FixedVal(0));
+    assert_eq!(*cell.get(), FixedVal(42));
+}
+
+#[test]
+fn stable_cell_round_trips_bounded_value_through_reinit() {
+    let memory = VectorMemory::default();
+    let mut cell = StableCell::init(memory.clone(), BoundedVal(String::new()));
+    cell.set(BoundedVal("hello".to_string()));
+
+    let cell = StableCell::init(memory, BoundedVal(String::new()));
+    assert_eq!(*cell.get(), BoundedVal("hello".to_string()));
+}
+
+#[test]
+fn stable_vec_round_trips_fixed_size_elements_through_reinit() {
+    let memory = VectorMemory::default();
+    let mut vec = StableVec::<FixedVal, _>::init(memory.clone());
+    vec.push(&FixedVal(1));
+    vec.push(&FixedVal(2));
+
+    let vec = StableVec::<FixedVal, _>::init(memory);
+    assert_eq!(vec.len(), 2);
+    assert_eq!(vec.get(0), Some(FixedVal(1)));
+    assert_eq!(vec.get(1), Some(FixedVal(2)));
+}
+
+#[test]
+fn stable_vec_round_trips_bounded_elements_through_reinit() {
+    let memory = VectorMemory::default();
+    let mut vec = StableVec::<BoundedVal, _>::init(memory.clone());
+    vec.push(&BoundedVal("a".to_string()));
+    vec.push(&BoundedVal("bcd".to_string()));
+
+    let vec = StableVec::<BoundedVal, _>::init(memory);
+    assert_eq!(vec.len(), 2);
+    assert_eq!(vec.get(0), Some(BoundedVal("a".to_string())));
+    assert_eq!(vec.get(1), Some(BoundedVal("bcd".to_string())));
+}
+
+#[test]
+fn buffered_stable_reader_supports_bufread_read_line() {
+    let memory = VectorMemory::default();
+    StableWriter::with_memory(memory.clone(), 0)
+        .write(b"line one\nline two\n")
+        .expect("write should succeed");
+
+    let mut reader = BufferedStableReader::with_reader(4, StableReader::with_memory(memory, 0));
+
+    let mut line = String::new();
+    reader.read_line(&mut line).expect("read_line should succeed");
+    assert_eq!(line, "line one\n");
+
+    line.clear();
+    reader.read_line(&mut line).expect("read_line should succeed");
+    assert_eq!(line, "line two\n");
+}
+
+#[test]
+fn buffered_stable_writer_into_inner_flushes_and_returns_writer() {
+    let memory = VectorMemory::default();
+    let mut writer =
+        BufferedStableWriter::with_writer(16, StableWriter::with_memory(memory.clone(), 0));
+    writer.write_all(b"hello").expect("write should succeed");
+
+    let inner = writer.into_inner().expect("flush should succeed");
+    assert_eq!(inner.offset(), 5);
+
+    let mut buf = [0u8; 5];
+    StableReader::with_memory(memory, 0)
+        .read(&mut buf)
+        .expect("read should succeed");
+    assert_eq!(&buf, b"hello");
+}
+
+#[test]
+fn write_vectored_read_vectored_span_a_grow() {
+    let memory = VectorMemory::default();
+    assert_eq!(memory.stable_size(), 0);
+
+    let mut writer = StableWriter::with_memory(memory.clone(), 0);
+    let parts = [io::IoSlice::new(b"hello "), io::IoSlice::new(b"world")];
+    let written = writer
+        .write_vectored(&parts)
+        .expect("write_vectored should succeed");
+    assert_eq!(written, 11);
+    assert!(memory.stable_size() > 0, "write_vectored should have grown the memory");
+
+    let mut first = [0u8; 6];
+    let mut second = [0u8; 5];
+    let mut reader = StableReader::with_memory(memory, 0);
+    let mut bufs = [
+        io::IoSliceMut::new(&mut first),
+        io::IoSliceMut::new(&mut second),
+    ];
+    let read = reader
+        .read_vectored(&mut bufs)
+        .expect("read_vectored should succeed");
+    assert_eq!(read, 11);
+    assert_eq!(&first, b"hello ");
+    assert_eq!(&second, b"world");
+}
+
+#[test]
+fn stable_reader_with_memory_refreshing_sees_data_written_after_construction() {
+    let memory = VectorMemory::default();
+    memory.stable_grow(1).expect("grow should succeed");
+
+    let mut reader = StableReader::with_memory_refreshing(memory.clone(), 0);
+
+    // Grow and write past the capacity the reader cached at construction.
+    let page_bytes = WASM_PAGE_SIZE_IN_BYTES as u64;
+    memory.stable_grow(1).expect("grow should succeed");
+    memory.stable_write(page_bytes as u32, b"fresh");
+
+    reader
+        .seek(SeekFrom::Start(page_bytes))
+        .expect("seek should succeed");
+    let mut buf = [0u8; 5];
+    reader.read(&mut buf).expect("refreshing read should succeed");
+    assert_eq!(&buf, b"fresh");
+}
+
+#[test]
+#[should_panic(expected = "OutOfBounds")]
+fn stable_reader_without_refresh_does_not_see_data_written_after_construction() {
+    let memory = VectorMemory::default();
+    memory.stable_grow(1).expect("grow should succeed");
+
+    let mut reader = StableReader::with_memory(memory.clone(), 0);
+
+    let page_bytes = WASM_PAGE_SIZE_IN_BYTES as u64;
+    memory.stable_grow(1).expect("grow should succeed");
+    memory.stable_write(page_bytes as u32, b"fresh");
+
+    reader
+        .seek(SeekFrom::Start(page_bytes))
+        .expect("seek should succeed");
+    let mut buf = [0u8; 5];
+    reader.read(&mut buf).unwrap();
+}
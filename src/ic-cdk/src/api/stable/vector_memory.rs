@@ -0,0 +1,173 @@
+//! In-memory and file-backed [`StableMemory`] implementations, so canister
+//! logic written against `StableWriter`/`StableReader` can be exercised
+//! without a replica.
+
+use std::cell::RefCell;
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::rc::Rc;
+
+#[cfg(target_arch = "wasm32")]
+use super::CanisterStableMemory;
+use super::{StableMemory, StableMemoryError};
+
+const WASM_PAGE_SIZE_IN_BYTES: usize = 64 * 1024;
+
+/// A [`StableMemory`] backed by a `Vec<u8>` kept in memory.
+///
+/// Honors the same contract as the real stable memory API: `stable_grow`
+/// appends zeroed pages and returns the previous page count, and
+/// `stable_write`/`stable_read` panic if the requested range exceeds the
+/// current size, exactly like the real API does.
+#[derive(Clone, Default)]
+pub struct VectorMemory {
+    bytes: Rc<RefCell<Vec<u8>>>,
+}
+
+impl StableMemory for VectorMemory {
+    fn stable_size(&self) -> u32 {
+        self.stable64_size() as u32
+    }
+
+    fn stable64_size(&self) -> u64 {
+        (self.bytes.borrow().len() / WASM_PAGE_SIZE_IN_BYTES) as u64
+    }
+
+    fn stable_grow(&self, new_pages: u32) -> Result<u32, StableMemoryError> {
+        self.stable64_grow(new_pages as u64).map(|old| old as u32)
+    }
+
+    fn stable64_grow(&self, new_pages: u64) -> Result<u64, StableMemoryError> {
+        let mut bytes = self.bytes.borrow_mut();
+        let old_page_count = bytes.len() / WASM_PAGE_SIZE_IN_BYTES;
+        let new_len = bytes.len() + new_pages as usize * WASM_PAGE_SIZE_IN_BYTES;
+        bytes.resize(new_len, 0);
+        Ok(old_page_count as u64)
+    }
+
+    fn stable_write(&self, offset: u32, buf: &[u8]) {
+        self.stable64_write(offset as u64, buf)
+    }
+
+    fn stable64_write(&self, offset: u64, buf: &[u8]) {
+        let mut bytes = self.bytes.borrow_mut();
+        let offset = offset as usize;
+        assert!(
+            offset + buf.len() <= bytes.len(),
+            "VectorMemory: write exceeds the current size of stable memory"
+        );
+        bytes[offset..offset + buf.len()].copy_from_slice(buf);
+    }
+
+    fn stable_read(&self, offset: u32, buf: &mut [u8]) {
+        self.stable64_read(offset as u64, buf)
+    }
+
+    fn stable64_read(&self, offset: u64, buf: &mut [u8]) {
+        let bytes = self.bytes.borrow();
+        let offset = offset as usize;
+        assert!(
+            offset + buf.len() <= bytes.len(),
+            "VectorMemory: read exceeds the current size of stable memory"
+        );
+        buf.copy_from_slice(&bytes[offset..offset + buf.len()]);
+    }
+}
+
+/// A [`StableMemory`] backed by a file on disk.
+///
+/// Useful for tests that want state to survive across process restarts,
+/// simulating a canister upgrade.
+#[derive(Clone)]
+pub struct FileMemory {
+    file: Rc<RefCell<File>>,
+}
+
+impl FileMemory {
+    /// Wraps `file` as a `StableMemory`. The file's current length must be a
+    /// multiple of the WASM page size.
+    pub fn new(file: File) -> Self {
+        Self {
+            file: Rc::new(RefCell::new(file)),
+        }
+    }
+}
+
+impl StableMemory for FileMemory {
+    fn stable_size(&self) -> u32 {
+        self.stable64_size() as u32
+    }
+
+    fn stable64_size(&self) -> u64 {
+        let len = self
+            .file
+            .borrow()
+            .metadata()
+            .expect("FileMemory: failed to read file metadata")
+            .len();
+        len / WASM_PAGE_SIZE_IN_BYTES as u64
+    }
+
+    fn stable_grow(&self, new_pages: u32) -> Result<u32, StableMemoryError> {
+        self.stable64_grow(new_pages as u64).map(|old| old as u32)
+    }
+
+    fn stable64_grow(&self, new_pages: u64) -> Result<u64, StableMemoryError> {
+        let mut file = self.file.borrow_mut();
+        let old_len = file
+            .metadata()
+            .expect("FileMemory: failed to read file metadata")
+            .len();
+        let old_page_count = old_len / WASM_PAGE_SIZE_IN_BYTES as u64;
+        let new_len = old_len + new_pages * WASM_PAGE_SIZE_IN_BYTES as u64;
+        file.set_len(new_len)
+            .map_err(|_| StableMemoryError::OutOfMemory)?;
+        Ok(old_page_count)
+    }
+
+    fn stable_write(&self, offset: u32, buf: &[u8]) {
+        self.stable64_write(offset as u64, buf)
+    }
+
+    fn stable64_write(&self, offset: u64, buf: &[u8]) {
+        let mut file = self.file.borrow_mut();
+        let len = file
+            .metadata()
+            .expect("FileMemory: failed to read file metadata")
+            .len();
+        assert!(
+            offset + buf.len() as u64 <= len,
+            "FileMemory: write exceeds the current size of stable memory"
+        );
+        file.seek(SeekFrom::Start(offset))
+            .expect("FileMemory: write exceeds the current size of stable memory");
+        file.write_all(buf)
+            .expect("FileMemory: write exceeds the current size of stable memory");
+    }
+
+    fn stable_read(&self, offset: u32, buf: &mut [u8]) {
+        self.stable64_read(offset as u64, buf)
+    }
+
+    fn stable64_read(&self, offset: u64, buf: &mut [u8]) {
+        let mut file = self.file.borrow_mut();
+        file.seek(SeekFrom::Start(offset))
+            .expect("FileMemory: read exceeds the current size of stable memory");
+        file.read_exact(buf)
+            .expect("FileMemory: read exceeds the current size of stable memory");
+    }
+}
+
+/// The `StableMemory` implementation used by default: the real IC stable
+/// memory API under `wasm32`, and an in-memory [`VectorMemory`] everywhere
+/// else, so the same canister logic compiles and runs under `cargo test` on
+/// the host.
+#[cfg(target_arch = "wasm32")]
+pub type DefaultMemoryImpl = CanisterStableMemory;
+
+/// The `StableMemory` implementation used by default: the real IC stable
+/// memory API under `wasm32`, and an in-memory [`VectorMemory`] everywhere
+/// else, so the same canister logic compiles and runs under `cargo test` on
+/// the host.
+#[cfg(not(target_arch = "wasm32"))]
+pub type DefaultMemoryImpl = VectorMemory;